@@ -1,12 +1,14 @@
 //! Derive a builder for a struct
 //!
 //! This crate implements the [builder pattern].
-//! When applied to a struct, it will derive **setter-methods** for all struct fields
-//! — the way you want it.
+//! When applied to a struct `Foo`, it derives a companion `FooBuilder` struct
+//! with one `Option<T>` field per field of `Foo`, setter methods that fill
+//! those fields in, and a fallible `build()` method that assembles a `Foo`
+//! once every field has been set.
 //!
 //! # Quick Start
 //!
-//! ## Generate Setters
+//! ## Generate a Builder
 //!
 //! ```rust
 //! #[macro_use] extern crate derive_builder;
@@ -19,64 +21,68 @@
 //! # fn main() {}
 //! ```
 //!
-//! `#[derive(Builder)]` will automatically generate a setter method for the `ipsum` field,
-//! looking like this:
+//! `#[derive(Builder)]` will automatically generate a `LoremBuilder` struct
+//! alongside `Lorem`, with a setter method for the `ipsum` field, looking
+//! like this:
 //!
 //! ```rust,ignore
-//! pub fn ipsum<VALUE: Into<String>>(&mut self, value: VALUE) -> &mut Self {
-//!     self.ipsum = value.into();
-//!     self
+//! #[derive(Clone, Default)]
+//! pub struct LoremBuilder {
+//!     ipsum: Option<String>,
+//! }
+//!
+//! impl LoremBuilder {
+//!     pub fn ipsum<VALUE: Into<String>>(&mut self, value: VALUE) -> &mut Self {
+//!         self.ipsum = Some(value.into());
+//!         self
+//!     }
+//!
+//!     pub fn build(&self) -> Result<Lorem, LoremBuilderError> {
+//!         Ok(Lorem {
+//!             ipsum: self.ipsum.clone().ok_or_else(|| /* ... */)?,
+//!         })
+//!     }
 //! }
 //! ```
 //!
 //! By default all generated setter-methods take and return `&mut self`
-//! (aka _non-conusuming_ builder pattern). Don't worry, you can easily opt into different
+//! (aka _non-consuming_ builder pattern). Don't worry, you can easily opt into different
 //! patterns and control many other aspects.
 //!
-//! ## Add a Build Method
+//! ## Use the Builder
 //!
-//! Ok, we've got setters. To complete the builder pattern you only have to implement at least
-//! one method which actually builds something based on the struct.
-//!
-//! These custom build methods of yours should also take `&mut self`, if you stick with the
-//! non-consuming pattern.
-//!
-//! This could look like:
+//! Because `LoremBuilder` derives `Default`, you start from
+//! `LoremBuilder::default()`, fill in the fields you need, and call `build()`.
+//! `build()` returns `Err` for the first field it finds unset, so there is no
+//! way to end up with a half-initialized `Lorem`.
 //!
 //! ```rust
 //! #[macro_use] extern crate derive_builder;
 //!
-//! #[derive(Builder, Default)]
+//! #[derive(Builder)]
 //! struct Lorem {
 //!     ipsum: String,
-//!     // ..
-//! }
-//!
-//! impl Lorem {
-//!     pub fn build(&self) -> String {
-//!         format!("The meaning of life is {}.", self.ipsum)
-//!     }
 //! }
 //!
 //! fn main() {
-//!     let x = Lorem::default().ipsum("42").build();
-//!     println!("{:?}", x);
+//!     let x = LoremBuilder::default().ipsum("42").build().unwrap();
+//!     assert_eq!(x.ipsum, "42");
 //! }
 //! ```
 //!
 //! # Builder Patterns
 //!
-//! Let's look again at `let x = Lorem::default().ipsum("42").build()`.
+//! Let's look again at `LoremBuilder::default().ipsum("42").build()`.
 //! Chaining method calls is nice, but what if `ipsum("42")` should only happen if `geek = true`?
 //!
 //! So let's make this call conditional
 //!
 //! ```rust,ignore
-//! let mut builder = Lorem::default();
+//! let mut builder = LoremBuilder::default();
 //! if geek {
 //!     builder.ipsum("42");
 //! }
-//! let x = builder.build();
+//! let x = builder.build()?;
 //! ```
 //!
 //! Now it comes in handy that our setter methods takes and returns a mutable reference. Otherwise
@@ -92,7 +98,7 @@
 //!
 //! Precede your struct with `#[setters(owned)]` to opt into this pattern.
 //!
-//! * Setters take and return `self`.
+//! * Setters take and return `self`; `build` still takes `&self`.
 //! * PRO: Setter calls and final build method can be chained.
 //! * CON: If you don't chain your calls, you have to create a reference to each return value,
 //!   e.g. `builder = builder.ipsum("42")`.
@@ -127,22 +133,19 @@
 //!
 //! # More Features
 //!
-//! We'll pretend that `clone()` is our build method for the following examples, to keep them as
-//! short as possible.
-//!
 //! ## Generic structs
 //!
 //! ```rust
 //! #[macro_use] extern crate derive_builder;
 //!
-//! #[derive(Builder, Debug, PartialEq, Default, Clone)]
-//! struct GenLorem<T> {
+//! #[derive(Builder, Debug, PartialEq, Clone)]
+//! struct GenLorem<T: Clone> {
 //!     ipsum: String,
 //!     dolor: T,
 //! }
 //!
 //! fn main() {
-//!     let x = GenLorem::default().ipsum("sit").dolor(42).clone();
+//!     let x = GenLoremBuilder::default().ipsum("sit").dolor(42).build().unwrap();
 //!     assert_eq!(x, GenLorem { ipsum: "sit".into(), dolor: 42 });
 //! }
 //! ```
@@ -182,12 +185,127 @@
 //!
 //! Otherwise precede your struct with `#[setters(private)]` to opt into private setters.
 //!
+//! For anything more specific than `public`/`private`, use
+//! `#[setters(vis = "pub(crate)")]` (struct-level) or
+//! `#[setter(vis = "pub(crate)")]` (field-level, overrides the struct
+//! default for that one field) to splice in an arbitrary visibility, e.g.
+//! `pub(crate)` or `pub(super)`. The same `vis = "..."` option works on
+//! `#[getters(...)]` / `#[getter(...)]`.
+//!
+//! ## Forwarding Arbitrary Attributes
+//!
+//! The built-in doc/cfg/allow passthrough covers the common cases, but if a
+//! field needs an attribute outside that whitelist on its generated
+//! setter(s) — `#[inline]`, `#[must_use]`, a helper attribute from another
+//! derive — add `#[builder_setter_attr(...)]` to the field:
+//!
+//! ```rust
+//! #[macro_use] extern crate derive_builder;
+//!
+//! #[derive(Builder)]
+//! struct Lorem {
+//!     #[builder_setter_attr(must_use)]
+//!     ipsum: String,
+//! }
+//! # fn main() {}
+//! ```
+//!
+//! ## Getters
+//!
+//! Getters are opt-in. Precede your struct with `#[getters]` (or
+//! `#[getters(...)]`) to generate a `fn field(&self) -> &Ty` accessor on
+//! `Foo` itself for every field. Add `#[getters(mutable)]` to also generate
+//! `fn field_mut(&mut self) -> &mut Ty`, and `#[getters(private)]` to make
+//! the generated getters private instead of `pub`.
+//!
+//! Per field, `#[getter(skip)]` omits the accessor, `#[getter(copy)]`
+//! returns `Ty` by value instead of `&Ty` (only correct for `Copy` types),
+//! and `#[getter(mutable)]` / `#[getter(immutable)]` / `#[getter(public)]` /
+//! `#[getter(private)]` override the struct-level default for that field.
+//!
+//! ## Collections
+//!
+//! ```rust
+//! #[macro_use] extern crate derive_builder;
+//! use std::collections::HashMap;
+//!
+//! #[derive(Builder)]
+//! struct Lorem {
+//!     #[setter(each = "item")]
+//!     ipsum: Vec<String>,
+//!     #[setter(each = "datum")]
+//!     dolor: HashMap<String, u32>,
+//! }
+//!
+//! fn main() {
+//!     let x = LoremBuilder::default()
+//!         .item("sit").item("amet")
+//!         .datum("sit", 1u32).datum("amet", 2u32)
+//!         .build().unwrap();
+//!     assert_eq!(x.ipsum, vec!["sit".to_string(), "amet".to_string()]);
+//!     assert_eq!(x.dolor.get("sit"), Some(&1));
+//! }
+//! ```
+//!
+//! `#[setter(each = "...")]` adds a singular setter alongside the regular
+//! whole-collection setter. The accumulator starts out as
+//! `Default::default()` if the field was never set as a whole. For a field
+//! whose type has a single generic type parameter (`Vec<T>`, `HashSet<T>`,
+//! ...) the singular setter appends one element at a time via the
+//! collection's `Extend` impl. For a field shaped like a map (two generic
+//! type parameters, e.g. `HashMap<K, V>`, `BTreeMap<K, V>`) the singular
+//! setter instead takes a key and a value and calls `.insert(key, value)`.
+//! Any other number of generic type parameters is a compile-time error.
+//!
+//! ## Optional Fields with Defaults
+//!
+//! Fields marked `#[builder(default)]` are no longer required: `build()`
+//! fills them in with `Default::default()` instead of failing when they
+//! were never set. `#[builder(default = "expr")]` evaluates `expr` (parsed
+//! as a Rust expression) instead.
+//!
+//! ```rust
+//! #[macro_use] extern crate derive_builder;
+//!
+//! #[derive(Builder)]
+//! struct Lorem {
+//!     ipsum: String,
+//!     #[builder(default = "42")]
+//!     dolor: i32,
+//!     #[builder(default)]
+//!     sit: Option<String>,
+//! }
+//!
+//! fn main() {
+//!     let x = LoremBuilder::default().ipsum("hi").build().unwrap();
+//!     assert_eq!(x.dolor, 42);
+//! }
+//! ```
+//!
+//! A struct-level `#[builder(default)]` has the same effect for every field
+//! at once, by falling back to a single `Foo::default()` base value (so
+//! `Foo` itself must implement `Default`) instead of each field's own type
+//! default.
+//!
+//! ## `no_std`
+//!
+//! `derive_builder` has a `std` feature, on by default; it controls only
+//! whether the derive itself initializes `env_logger`. The generated code
+//! is emitted with `#[cfg(feature = "std")]` / `#[cfg(not(feature =
+//! "std"))]` branches, which are evaluated in **your** crate, so it is your
+//! crate's own `std` feature that decides: with it on, the generated error
+//! types implement `std::error::Error`; with it off (or with no `std`
+//! feature at all) they implement no `Error` trait and rely solely on
+//! `core`. The generated `FooBuilder` itself never implements `Error`.
+//!
 //! ## Gotchas
 //!
 //! - Tuple structs and unit structs are not supported as they have no field
 //!   names.
 //! - When defining a generic struct, you cannot use `VALUE` as a generic
 //!   parameter as this is what all setters are using.
+//! - `build()` returns `Err` as soon as the first unset required field is
+//!   encountered; it does not collect every missing field.
 //!
 //! [builder pattern]: https://aturon.github.io/ownership/builders.html
 
@@ -199,17 +317,22 @@ extern crate syn;
 extern crate quote;
 #[macro_use]
 extern crate log;
+#[cfg(feature = "std")]
 extern crate env_logger;
 
 mod options;
 
 use proc_macro::TokenStream;
-use options::{Options, SetterPattern};
+use options::{forwarded_setter_attrs, FieldBuilderOptions, FieldDefault, FieldGetterOptions, FieldSetterOptions, Options, SetterPattern};
 
 #[doc(hidden)]
-#[proc_macro_derive(Builder, attributes(setters, getters, setter, getter))]
+#[proc_macro_derive(Builder, attributes(setters, getters, setter, getter, builder, builder_setter_attr))]
 pub fn derive(input: TokenStream) -> TokenStream {
-    env_logger::init().unwrap();
+    // `derive` runs once per `#[derive(Builder)]` in the same compiler
+    // process, so a second invocation must not panic on an already-initialized
+    // global logger.
+    #[cfg(feature = "std")]
+    let _ = env_logger::init();
 
     let input = input.to_string();
 
@@ -225,7 +348,7 @@ fn filter_attr(attr: &&syn::Attribute) -> bool {
         return false
     }
 
-    if attr.is_sugared_doc == true {
+    if attr.is_sugared_doc {
         if let syn::MetaItem::NameValue(ref ident, _) = attr.value {
             // example:
             // Attribute { style: Outer, value: NameValue(Ident("doc"), Str("/// This is a doc comment for a field", Cooked)), is_sugared_doc: true }
@@ -233,29 +356,59 @@ fn filter_attr(attr: &&syn::Attribute) -> bool {
                 return true
             }
         }
-    } else {
-        if let syn::MetaItem::List(ref ident, _) = attr.value {
-            // example:
-            // Attribute { style: Outer, value: List(Ident("allow"), [MetaItem(Word(Ident("non_snake_case")))]), is_sugared_doc: false }
-            return match ident.as_ref() {
-                "cfg" => true,
-                "allow" => true,
-                _ => false,
+    } else if let syn::MetaItem::List(ref ident, _) = attr.value {
+        // example:
+        // Attribute { style: Outer, value: List(Ident("allow"), [MetaItem(Word(Ident("non_snake_case")))]), is_sugared_doc: false }
+        return matches!(ident.as_ref(), "cfg" | "allow")
+    }
+    false
+}
+
+/// Turns `FooBar` into `foo_bar`, for generating a unique, snake_case module
+/// name to stash the per-derive error runtime in.
+fn to_snake_case(name: &str) -> String {
+    let mut out = String::with_capacity(name.len() + 4);
+    for (i, c) in name.chars().enumerate() {
+        if c.is_uppercase() {
+            if i != 0 {
+                out.push('_');
             }
+            out.extend(c.to_lowercase());
+        } else {
+            out.push(c);
         }
     }
-    false
+    out
+}
+
+/// Extracts the generic type arguments of a path type, e.g. `[T]` out of
+/// `Vec<T>` or `[K, V]` out of `HashMap<K, V>`. Used to type the singular
+/// `each` setter, which needs to tell single-type-param collections
+/// (`Vec`, `HashSet`, ...) apart from map-shaped two-type-param ones
+/// (`HashMap`, `BTreeMap`, ...).
+fn generic_args(ty: &syn::Ty) -> Option<&[syn::Ty]> {
+    if let syn::Ty::Path(_, ref path) = *ty {
+        if let Some(segment) = path.segments.last() {
+            if let syn::PathParameters::AngleBracketed(ref data) = segment.parameters {
+                if !data.types.is_empty() {
+                    return Some(&data.types);
+                }
+            }
+        }
+    }
+    None
 }
 
 fn builder_for_struct(ast: syn::MacroInput) -> quote::Tokens {
     debug!("Deriving Builder for '{}'.", ast.ident);
     let opts = Options::from(ast.attrs);
     if !opts.setter_enabled() {
-        trace!("Setters disabled for '{}'.", ast.ident);
+        trace!("Builder disabled for '{}'.", ast.ident);
         return quote!();
     }
-    debug!("Deriving Setters for '{}'.", ast.ident);
+    debug!("Deriving Builder for '{}'.", ast.ident);
     let setter_pattern = opts.setter_pattern();
+    let vis = opts.setter_visibility();
 
     let fields = match ast.body {
         syn::Body::Struct(syn::VariantData::Struct(ref fields)) => fields,
@@ -263,54 +416,347 @@ fn builder_for_struct(ast: syn::MacroInput) -> quote::Tokens {
     };
 
     let name = &ast.ident;
+    let builder_name = syn::Ident::new(format!("{}Builder", name));
+    let error_name = syn::Ident::new(format!("{}BuilderError", name));
+    let error_mod_name = syn::Ident::new(format!("__{}_builder_error", to_snake_case(name.as_ref())));
     let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
-    let funcs = fields.iter().map(|f| {
+
+    let field_attrs: Vec<_> = fields.iter().map(|f| {
+        f.attrs.iter().filter(filter_attr).collect::<Vec<_>>()
+    }).collect();
+
+    let builder_fields = fields.iter().zip(field_attrs.iter()).map(|(f, attrs)| {
         let f_name = &f.ident;
         let ty = &f.ty;
+        quote!(
+            #(#attrs)*
+            #f_name: Option<#ty>
+        )
+    });
 
-        trace!("Filtering field attributes");
-        let attrs = f.attrs.iter()
-            .filter(|a| {
-                let keep = filter_attr(a);
-                match keep {
-                    true => trace!("Keeping field attribute for setter {:?}", a),
-                    false => trace!("Ignoring field attribute {:?}", a)
-                }
-                keep
-            });
+    let setter_funcs = fields.iter().zip(field_attrs.iter()).map(|(f, attrs)| {
+        let f_name = &f.ident;
+        let ty = &f.ty;
 
-        let vis = opts.setter_visibility();
-        debug!("Setter visibility = {:?}", vis);
+        let field_setter_opts = FieldSetterOptions::from(&f.attrs);
+        let field_vis = field_setter_opts.vis().unwrap_or_else(|| vis.clone());
+        let forwarded = forwarded_setter_attrs(&f.attrs);
 
-        match *setter_pattern {
+        let whole_setter_forwarded = forwarded.clone();
+        let whole_setter = match *setter_pattern {
             SetterPattern::Owned => quote!(
                     #(#attrs)*
-                    #vis fn #f_name<VALUE: Into<#ty>>(self, value: VALUE) -> Self {
-                        let mut new = self;
-                        new.#f_name = value.into();
-                        new
+                    #(#whole_setter_forwarded)*
+                    #field_vis fn #f_name<VALUE: Into<#ty>>(mut self, value: VALUE) -> Self {
+                        self.#f_name = Some(value.into());
+                        self
                 }),
             SetterPattern::Mutable => quote!(
                     #(#attrs)*
-                    #vis fn #f_name<VALUE: Into<#ty>>(&mut self, value: VALUE) -> &mut Self {
-                        let mut new = self;
-                        new.#f_name = value.into();
-                        new
+                    #(#whole_setter_forwarded)*
+                    #field_vis fn #f_name<VALUE: Into<#ty>>(&mut self, value: VALUE) -> &mut Self {
+                        self.#f_name = Some(value.into());
+                        self
                 }),
             SetterPattern::Immutable => quote!(
                     #(#attrs)*
-                    #vis fn #f_name<VALUE: Into<#ty>>(&self, value: VALUE) -> Self {
+                    #(#whole_setter_forwarded)*
+                    #field_vis fn #f_name<VALUE: Into<#ty>>(&self, value: VALUE) -> Self {
                         let mut new = self.clone();
-                        new.#f_name = value.into();
+                        new.#f_name = Some(value.into());
                         new
                 }),
+        };
+
+        let each_setter = match field_setter_opts.each() {
+            Some(each_name) => {
+                let each_ident = syn::Ident::new(each_name);
+                let type_args = generic_args(ty).unwrap_or_else(|| {
+                    panic!("#[setter(each = \"{}\")] requires a generic collection field type", each_name)
+                });
+
+                match type_args.len() {
+                    // `Vec<T>`, `HashSet<T>`, ... - a single singular item, appended via `Extend`.
+                    1 => {
+                        let item_ty = &type_args[0];
+                        match *setter_pattern {
+                            SetterPattern::Owned => quote!(
+                                #(#attrs)*
+                                #(#forwarded)*
+                                #field_vis fn #each_ident<VALUE: Into<#item_ty>>(mut self, value: VALUE) -> Self {
+                                    self.#f_name.get_or_insert_with(Default::default)
+                                        .extend(Some(value.into()));
+                                    self
+                                }),
+                            SetterPattern::Mutable => quote!(
+                                #(#attrs)*
+                                #(#forwarded)*
+                                #field_vis fn #each_ident<VALUE: Into<#item_ty>>(&mut self, value: VALUE) -> &mut Self {
+                                    self.#f_name.get_or_insert_with(Default::default)
+                                        .extend(Some(value.into()));
+                                    self
+                                }),
+                            SetterPattern::Immutable => quote!(
+                                #(#attrs)*
+                                #(#forwarded)*
+                                #field_vis fn #each_ident<VALUE: Into<#item_ty>>(&self, value: VALUE) -> Self {
+                                    let mut new = self.clone();
+                                    new.#f_name.get_or_insert_with(Default::default)
+                                        .extend(Some(value.into()));
+                                    new
+                                }),
+                        }
+                    }
+                    // `HashMap<K, V>`, `BTreeMap<K, V>`, ... - a key/value pair, added via `.insert(...)`.
+                    2 => {
+                        let key_ty = &type_args[0];
+                        let value_ty = &type_args[1];
+                        match *setter_pattern {
+                            SetterPattern::Owned => quote!(
+                                #(#attrs)*
+                                #(#forwarded)*
+                                #field_vis fn #each_ident<KEY: Into<#key_ty>, VALUE: Into<#value_ty>>(mut self, key: KEY, value: VALUE) -> Self {
+                                    self.#f_name.get_or_insert_with(Default::default)
+                                        .insert(key.into(), value.into());
+                                    self
+                                }),
+                            SetterPattern::Mutable => quote!(
+                                #(#attrs)*
+                                #(#forwarded)*
+                                #field_vis fn #each_ident<KEY: Into<#key_ty>, VALUE: Into<#value_ty>>(&mut self, key: KEY, value: VALUE) -> &mut Self {
+                                    self.#f_name.get_or_insert_with(Default::default)
+                                        .insert(key.into(), value.into());
+                                    self
+                                }),
+                            SetterPattern::Immutable => quote!(
+                                #(#attrs)*
+                                #(#forwarded)*
+                                #field_vis fn #each_ident<KEY: Into<#key_ty>, VALUE: Into<#value_ty>>(&self, key: KEY, value: VALUE) -> Self {
+                                    let mut new = self.clone();
+                                    new.#f_name.get_or_insert_with(Default::default)
+                                        .insert(key.into(), value.into());
+                                    new
+                                }),
+                        }
+                    }
+                    n => panic!(
+                        "#[setter(each = \"{}\")] only supports collection fields with 1 (Vec, HashSet, ...) \
+                         or 2 (HashMap, BTreeMap, ...) generic type parameters, found {}",
+                        each_name, n
+                    ),
+                }
+            }
+            None => quote!(),
+        };
+
+        quote!(#whole_setter #each_setter)
+    });
+
+    let getter_funcs: Vec<_> = if opts.getter_enabled() {
+        fields.iter().zip(field_attrs.iter()).filter_map(|(f, attrs)| {
+            let field_opts = FieldGetterOptions::from(&f.attrs);
+            if field_opts.skip() {
+                return None;
+            }
+
+            let f_name = &f.ident;
+            let ty = &f.ty;
+            let getter_vis = field_opts.visibility().unwrap_or(opts.getter_visibility());
+            let wants_mutable = field_opts.mutable().unwrap_or(opts.getter_mutable());
+
+            let getter = if field_opts.copy() {
+                quote!(
+                    #(#attrs)*
+                    #getter_vis fn #f_name(&self) -> #ty {
+                        self.#f_name
+                    }
+                )
+            } else {
+                quote!(
+                    #(#attrs)*
+                    #getter_vis fn #f_name(&self) -> &#ty {
+                        &self.#f_name
+                    }
+                )
+            };
+
+            let mut_getter = if wants_mutable {
+                let f_name_mut = syn::Ident::new(format!("{}_mut", f_name.as_ref().expect("named field")));
+                quote!(
+                    #(#attrs)*
+                    #getter_vis fn #f_name_mut(&mut self) -> &mut #ty {
+                        &mut self.#f_name
+                    }
+                )
+            } else {
+                quote!()
+            };
+
+            Some(quote!(#getter #mut_getter))
+        }).collect()
+    } else {
+        Vec::new()
+    };
+
+    let struct_default = opts.struct_default();
+    let needs_default_base = struct_default && fields.iter().any(|f| {
+        FieldBuilderOptions::from(&f.attrs).default().is_none()
+    });
+
+    let build_fields = fields.iter().zip(field_attrs.iter()).map(|(f, attrs)| {
+        let f_name = &f.ident;
+        let field_name_str = f_name.as_ref().map(|i| i.as_ref().to_string()).unwrap_or_default();
+        let field_builder_opts = FieldBuilderOptions::from(&f.attrs);
+
+        match field_builder_opts.default() {
+            Some(&FieldDefault::Default) => quote!(
+                #(#attrs)*
+                #f_name: self.#f_name.clone().unwrap_or_default()
+            ),
+            Some(FieldDefault::Expr(expr_str)) => {
+                let expr = syn::parse_expr(expr_str).expect("Couldn't parse #[builder(default = \"...\")] expression");
+                quote!(
+                    #(#attrs)*
+                    #f_name: match self.#f_name.clone() {
+                        Some(value) => value,
+                        None => #expr,
+                    }
+                )
+            }
+            None if struct_default => quote!(
+                #(#attrs)*
+                #f_name: self.#f_name.clone().unwrap_or_else(|| __default_base.#f_name.clone())
+            ),
+            // `#[setter(each = "...")]` fields are conceptually collections:
+            // never touching the singular setter means "empty", not
+            // "unset", so don't require the whole-collection setter either.
+            None if FieldSetterOptions::from(&f.attrs).each().is_some() => quote!(
+                #(#attrs)*
+                #f_name: self.#f_name.clone().unwrap_or_default()
+            ),
+            None => quote!(
+                #(#attrs)*
+                #f_name: self.#f_name.clone().ok_or_else(|| {
+                    #error_name::from(#error_mod_name::UninitializedFieldError::new(#field_name_str))
+                })?
+            ),
         }
     });
 
+    let default_base_let = if needs_default_base {
+        quote!(let __default_base: #name #ty_generics = Default::default();)
+    } else {
+        quote!()
+    };
+
+    // Whether the downstream crate gets `std`- or `core`-based error types is
+    // the *downstream* crate's own `std` feature, not ours: emit both bodies
+    // and let `#[cfg(feature = "std")]` pick between them at their build
+    // time, rather than deciding once here at our own build time.
     quote! {
+        #[allow(dead_code)]
+        mod #error_mod_name {
+            #[cfg(feature = "std")]
+            use std::fmt;
+            #[cfg(not(feature = "std"))]
+            pub extern crate core;
+            #[cfg(not(feature = "std"))]
+            use self::core::fmt;
+
+            #[derive(Debug, Clone, PartialEq, Eq)]
+            pub struct UninitializedFieldError(&'static str);
+
+            impl UninitializedFieldError {
+                pub fn new(field_name: &'static str) -> Self {
+                    UninitializedFieldError(field_name)
+                }
+
+                pub fn field_name(&self) -> &'static str {
+                    self.0
+                }
+            }
+
+            impl fmt::Display for UninitializedFieldError {
+                fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                    write!(f, "Field not initialized: {}", self.0)
+                }
+            }
+
+            #[cfg(feature = "std")]
+            impl ::std::error::Error for UninitializedFieldError {
+                fn description(&self) -> &str {
+                    "Field not initialized"
+                }
+            }
+        }
+
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        #vis enum #error_name {
+            UninitializedField(#error_mod_name::UninitializedFieldError),
+        }
+
+        #[cfg(feature = "std")]
+        impl ::std::fmt::Display for #error_name {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                match *self {
+                    #error_name::UninitializedField(ref e) => write!(f, "{}", e),
+                }
+            }
+        }
+
+        #[cfg(not(feature = "std"))]
+        impl #error_mod_name::core::fmt::Display for #error_name {
+            fn fmt(&self, f: &mut #error_mod_name::core::fmt::Formatter) -> #error_mod_name::core::fmt::Result {
+                match *self {
+                    #error_name::UninitializedField(ref e) => write!(f, "{}", e),
+                }
+            }
+        }
+
+        #[cfg(feature = "std")]
+        impl ::std::error::Error for #error_name {
+            fn description(&self) -> &str {
+                match *self {
+                    #error_name::UninitializedField(ref e) => ::std::error::Error::description(e),
+                }
+            }
+        }
+
+        #[cfg(feature = "std")]
+        impl ::std::convert::From<#error_mod_name::UninitializedFieldError> for #error_name {
+            fn from(e: #error_mod_name::UninitializedFieldError) -> Self {
+                #error_name::UninitializedField(e)
+            }
+        }
+
+        #[cfg(not(feature = "std"))]
+        impl #error_mod_name::core::convert::From<#error_mod_name::UninitializedFieldError> for #error_name {
+            fn from(e: #error_mod_name::UninitializedFieldError) -> Self {
+                #error_name::UninitializedField(e)
+            }
+        }
+
+        #[derive(Clone, Default)]
+        #[allow(dead_code)]
+        #vis struct #builder_name #ty_generics #where_clause {
+            #(#builder_fields,)*
+        }
+
+        #[allow(dead_code)]
+        impl #impl_generics #builder_name #ty_generics #where_clause {
+            #(#setter_funcs)*
+
+            pub fn build(&self) -> Result<#name #ty_generics, #error_name> {
+                #default_base_let
+                Ok(#name {
+                    #(#build_fields,)*
+                })
+            }
+        }
+
         #[allow(dead_code)]
         impl #impl_generics #name #ty_generics #where_clause {
-            #(#funcs)*
+            #(#getter_funcs)*
         }
     }
-}
\ No newline at end of file
+}
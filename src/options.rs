@@ -0,0 +1,379 @@
+use quote;
+use syn;
+
+/// Controls the `self`/`&mut self`/`&self` signature used for generated setters.
+/// The builder's `build()` method always takes `&self`, whatever the pattern.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SetterPattern {
+    Owned,
+    Mutable,
+    Immutable,
+}
+
+/// Visibility of generated items. `Custom` carries an arbitrary visibility
+/// string (`pub(crate)`, `pub(super)`, ...) supplied via `vis = "..."`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Visibility {
+    Public,
+    Private,
+    Custom(String),
+}
+
+impl quote::ToTokens for Visibility {
+    fn to_tokens(&self, tokens: &mut quote::Tokens) {
+        match *self {
+            Visibility::Public => tokens.append("pub"),
+            Visibility::Private => {}
+            Visibility::Custom(ref vis) => tokens.append(vis.as_str()),
+        }
+    }
+}
+
+/// Reads a `name = "literal"` pair out of a `#[...(...)]` nested meta item.
+fn str_value<'a>(ident: &syn::Ident, name: &str, lit: &'a syn::Lit) -> Option<&'a str> {
+    if ident != name {
+        return None;
+    }
+    match *lit {
+        syn::Lit::Str(ref s, _) => Some(s.as_str()),
+        _ => panic!("#[{} = \"...\"] expects a string literal", name),
+    }
+}
+
+/// Parsed `#[setters(...)]` / `#[getters(...)]` options for a struct.
+pub struct Options {
+    setter_enabled: bool,
+    setter_pattern: SetterPattern,
+    setter_visibility: Visibility,
+    getter_enabled: bool,
+    getter_mutable: bool,
+    getter_visibility: Visibility,
+    struct_default: bool,
+}
+
+impl Options {
+    pub fn from(attrs: Vec<syn::Attribute>) -> Options {
+        let mut setter_enabled = true;
+        let mut setter_pattern = SetterPattern::Mutable;
+        let mut setter_visibility = Visibility::Public;
+        let mut getter_enabled = false;
+        let mut getter_mutable = false;
+        let mut getter_visibility = Visibility::Public;
+        let mut struct_default = false;
+
+        for attr in &attrs {
+            match attr.value.name() {
+                "setters" => {
+                    if let syn::MetaItem::List(_, ref nested) = attr.value {
+                        for item in nested {
+                            match *item {
+                                syn::NestedMetaItem::MetaItem(syn::MetaItem::Word(ref ident)) => {
+                                    match ident.as_ref() {
+                                        "owned" => setter_pattern = SetterPattern::Owned,
+                                        "mutable" => setter_pattern = SetterPattern::Mutable,
+                                        "immutable" => setter_pattern = SetterPattern::Immutable,
+                                        "public" => setter_visibility = Visibility::Public,
+                                        "private" => setter_visibility = Visibility::Private,
+                                        "skip" => setter_enabled = false,
+                                        other => panic!("Unknown setters option '{}'", other),
+                                    }
+                                }
+                                syn::NestedMetaItem::MetaItem(syn::MetaItem::NameValue(ref ident, ref lit)) => {
+                                    if let Some(vis) = str_value(ident, "vis", lit) {
+                                        setter_visibility = Visibility::Custom(vis.to_string());
+                                    } else {
+                                        panic!("Unknown setters option '{}'", ident);
+                                    }
+                                }
+                                ref other => panic!("Unknown setters option '{:?}'", other),
+                            }
+                        }
+                    }
+                }
+                "getters" => {
+                    getter_enabled = true;
+                    if let syn::MetaItem::List(_, ref nested) = attr.value {
+                        for item in nested {
+                            match *item {
+                                syn::NestedMetaItem::MetaItem(syn::MetaItem::Word(ref ident)) => {
+                                    match ident.as_ref() {
+                                        "mutable" => getter_mutable = true,
+                                        "immutable" => getter_mutable = false,
+                                        "public" => getter_visibility = Visibility::Public,
+                                        "private" => getter_visibility = Visibility::Private,
+                                        "disabled" => getter_enabled = false,
+                                        other => panic!("Unknown getters option '{}'", other),
+                                    }
+                                }
+                                syn::NestedMetaItem::MetaItem(syn::MetaItem::NameValue(ref ident, ref lit)) => {
+                                    if let Some(vis) = str_value(ident, "vis", lit) {
+                                        getter_visibility = Visibility::Custom(vis.to_string());
+                                    } else {
+                                        panic!("Unknown getters option '{}'", ident);
+                                    }
+                                }
+                                ref other => panic!("Unknown getters option '{:?}'", other),
+                            }
+                        }
+                    }
+                }
+                "builder" => {
+                    if let syn::MetaItem::List(_, ref nested) = attr.value {
+                        for item in nested {
+                            if let syn::NestedMetaItem::MetaItem(syn::MetaItem::Word(ref ident)) = *item {
+                                match ident.as_ref() {
+                                    "default" => struct_default = true,
+                                    other => panic!("Unknown builder option '{}'", other),
+                                }
+                            }
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Options {
+            setter_enabled,
+            setter_pattern,
+            setter_visibility,
+            getter_enabled,
+            getter_mutable,
+            getter_visibility,
+            struct_default,
+        }
+    }
+
+    pub fn setter_enabled(&self) -> bool {
+        self.setter_enabled
+    }
+
+    pub fn setter_pattern(&self) -> &SetterPattern {
+        &self.setter_pattern
+    }
+
+    pub fn setter_visibility(&self) -> Visibility {
+        self.setter_visibility.clone()
+    }
+
+    pub fn getter_enabled(&self) -> bool {
+        self.getter_enabled
+    }
+
+    pub fn getter_mutable(&self) -> bool {
+        self.getter_mutable
+    }
+
+    pub fn getter_visibility(&self) -> Visibility {
+        self.getter_visibility.clone()
+    }
+
+    pub fn struct_default(&self) -> bool {
+        self.struct_default
+    }
+}
+
+/// The fallback value a field falls back to when left unset, as requested
+/// via `#[builder(default)]` / `#[builder(default = "...")]`.
+pub enum FieldDefault {
+    /// `#[builder(default)]`: fall back to `Default::default()`.
+    Default,
+    /// `#[builder(default = "expr")]`: fall back to evaluating `expr`.
+    Expr(String),
+}
+
+/// Parsed `#[builder(...)]` options for a single field.
+pub struct FieldBuilderOptions {
+    default: Option<FieldDefault>,
+}
+
+impl FieldBuilderOptions {
+    pub fn from(attrs: &[syn::Attribute]) -> FieldBuilderOptions {
+        let mut default = None;
+
+        for attr in attrs {
+            if attr.value.name() != "builder" {
+                continue;
+            }
+
+            if let syn::MetaItem::List(_, ref nested) = attr.value {
+                for item in nested {
+                    match *item {
+                        syn::NestedMetaItem::MetaItem(syn::MetaItem::Word(ref ident)) if ident == "default" => {
+                            default = Some(FieldDefault::Default);
+                        }
+                        syn::NestedMetaItem::MetaItem(syn::MetaItem::NameValue(ref ident, ref lit)) if ident == "default" => {
+                            if let syn::Lit::Str(ref s, _) = *lit {
+                                default = Some(FieldDefault::Expr(s.clone()));
+                            } else {
+                                panic!("#[builder(default = \"...\")] expects a string literal");
+                            }
+                        }
+                        ref other => panic!("Unknown builder option '{:?}'", other),
+                    }
+                }
+            }
+        }
+
+        FieldBuilderOptions { default }
+    }
+
+    pub fn default(&self) -> Option<&FieldDefault> {
+        self.default.as_ref()
+    }
+}
+
+/// Parsed `#[setter(...)]` options for a single field.
+pub struct FieldSetterOptions {
+    each: Option<String>,
+    vis: Option<String>,
+}
+
+impl FieldSetterOptions {
+    pub fn from(attrs: &[syn::Attribute]) -> FieldSetterOptions {
+        let mut each = None;
+        let mut vis = None;
+
+        for attr in attrs {
+            if attr.value.name() != "setter" {
+                continue;
+            }
+
+            if let syn::MetaItem::List(_, ref nested) = attr.value {
+                for item in nested {
+                    if let syn::NestedMetaItem::MetaItem(syn::MetaItem::NameValue(ref ident, ref lit)) = *item {
+                        if let Some(s) = str_value(ident, "each", lit) {
+                            each = Some(s.to_string());
+                        } else if let Some(s) = str_value(ident, "vis", lit) {
+                            vis = Some(s.to_string());
+                        } else {
+                            panic!("Unknown setter option '{}'", ident);
+                        }
+                    }
+                }
+            }
+        }
+
+        FieldSetterOptions { each, vis }
+    }
+
+    pub fn each(&self) -> Option<&str> {
+        self.each.as_deref()
+    }
+
+    pub fn vis(&self) -> Option<Visibility> {
+        self.vis.clone().map(Visibility::Custom)
+    }
+}
+
+/// Reconstructs a `#[...]` attribute's tokens from a parsed meta item, so it
+/// can be forwarded verbatim via `#[builder_setter_attr(...)]` even though it
+/// isn't part of the built-in `doc`/`cfg`/`allow` whitelist.
+fn meta_item_tokens(item: &syn::MetaItem) -> quote::Tokens {
+    match *item {
+        syn::MetaItem::Word(ref ident) => quote!(#ident),
+        syn::MetaItem::NameValue(ref ident, ref lit) => quote!(#ident = #lit),
+        syn::MetaItem::List(ref ident, ref nested) => {
+            let inner = nested.iter().map(nested_meta_item_tokens);
+            quote!(#ident(#(#inner),*))
+        }
+    }
+}
+
+fn nested_meta_item_tokens(item: &syn::NestedMetaItem) -> quote::Tokens {
+    match *item {
+        syn::NestedMetaItem::MetaItem(ref mi) => meta_item_tokens(mi),
+        syn::NestedMetaItem::Literal(ref lit) => quote!(#lit),
+    }
+}
+
+/// Any attributes listed under `#[builder_setter_attr(...)]` on a field,
+/// forwarded verbatim onto that field's generated setter(s).
+pub fn forwarded_setter_attrs(attrs: &[syn::Attribute]) -> Vec<quote::Tokens> {
+    attrs.iter()
+        .filter(|a| a.value.name() == "builder_setter_attr")
+        .flat_map(|a| {
+            if let syn::MetaItem::List(_, ref nested) = a.value {
+                nested.iter().map(|item| {
+                    let inner = nested_meta_item_tokens(item);
+                    quote!(#[#inner])
+                }).collect::<Vec<_>>()
+            } else {
+                Vec::new()
+            }
+        })
+        .collect()
+}
+
+/// Parsed `#[getter(...)]` options for a single field, overriding the
+/// struct-level `#[getters(...)]` defaults.
+pub struct FieldGetterOptions {
+    skip: bool,
+    mutable: Option<bool>,
+    copy: bool,
+    visibility: Option<Visibility>,
+}
+
+impl FieldGetterOptions {
+    pub fn from(attrs: &[syn::Attribute]) -> FieldGetterOptions {
+        let mut skip = false;
+        let mut mutable = None;
+        let mut copy = false;
+        let mut visibility = None;
+
+        for attr in attrs {
+            if attr.value.name() != "getter" {
+                continue;
+            }
+
+            if let syn::MetaItem::List(_, ref nested) = attr.value {
+                for item in nested {
+                    match *item {
+                        syn::NestedMetaItem::MetaItem(syn::MetaItem::Word(ref ident)) => {
+                            match ident.as_ref() {
+                                "skip" => skip = true,
+                                "mutable" => mutable = Some(true),
+                                "immutable" => mutable = Some(false),
+                                "copy" => copy = true,
+                                "public" => visibility = Some(Visibility::Public),
+                                "private" => visibility = Some(Visibility::Private),
+                                other => panic!("Unknown getter option '{}'", other),
+                            }
+                        }
+                        syn::NestedMetaItem::MetaItem(syn::MetaItem::NameValue(ref ident, ref lit)) => {
+                            if let Some(s) = str_value(ident, "vis", lit) {
+                                visibility = Some(Visibility::Custom(s.to_string()));
+                            } else {
+                                panic!("Unknown getter option '{}'", ident);
+                            }
+                        }
+                        ref other => panic!("Unknown getter option '{:?}'", other),
+                    }
+                }
+            }
+        }
+
+        FieldGetterOptions {
+            skip,
+            mutable,
+            copy,
+            visibility,
+        }
+    }
+
+    pub fn skip(&self) -> bool {
+        self.skip
+    }
+
+    pub fn mutable(&self) -> Option<bool> {
+        self.mutable
+    }
+
+    pub fn copy(&self) -> bool {
+        self.copy
+    }
+
+    pub fn visibility(&self) -> Option<Visibility> {
+        self.visibility.clone()
+    }
+}
@@ -0,0 +1,130 @@
+#[macro_use]
+extern crate derive_builder;
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+// Several structs deriving `Builder` in the same test binary: this is what
+// reproduced the env_logger double-init panic, since `derive()` runs once
+// per `#[derive(Builder)]` in the same compiler process.
+
+#[derive(Builder, Debug, PartialEq)]
+struct Basic {
+    ipsum: String,
+    dolor: i32,
+}
+
+#[test]
+fn basic_build() {
+    let x = BasicBuilder::default().ipsum("sit").dolor(42).build().unwrap();
+    assert_eq!(x, Basic { ipsum: "sit".to_string(), dolor: 42 });
+}
+
+#[test]
+fn basic_build_missing_field_errors() {
+    let err = BasicBuilder::default().ipsum("sit").build().unwrap_err();
+    assert_eq!(err.to_string(), "Field not initialized: dolor");
+}
+
+#[derive(Builder, Debug, PartialEq)]
+#[getters]
+struct WithGetters {
+    ipsum: String,
+}
+
+#[test]
+fn getters() {
+    let x = WithGettersBuilder::default().ipsum("sit").build().unwrap();
+    assert_eq!(x.ipsum(), &"sit".to_string());
+}
+
+#[derive(Builder, Debug, PartialEq)]
+struct WithDefaults {
+    #[builder(default)]
+    ipsum: String,
+    #[builder(default = "42")]
+    dolor: i32,
+}
+
+#[test]
+fn defaults_fill_in_unset_fields() {
+    let x = WithDefaultsBuilder::default().build().unwrap();
+    assert_eq!(x, WithDefaults { ipsum: String::new(), dolor: 42 });
+}
+
+#[test]
+fn defaults_are_overridable() {
+    let x = WithDefaultsBuilder::default().ipsum("sit").dolor(7).build().unwrap();
+    assert_eq!(x, WithDefaults { ipsum: "sit".to_string(), dolor: 7 });
+}
+
+#[derive(Builder, Debug, PartialEq, Default)]
+#[builder(default)]
+struct WithStructDefault {
+    ipsum: String,
+    dolor: i32,
+}
+
+#[test]
+fn struct_default_fills_in_unset_fields() {
+    let x = WithStructDefaultBuilder::default().dolor(7).build().unwrap();
+    assert_eq!(x, WithStructDefault { ipsum: String::new(), dolor: 7 });
+}
+
+#[derive(Builder, Debug, PartialEq)]
+struct WithEach {
+    #[setter(each = "item")]
+    ipsum: Vec<String>,
+    #[setter(each = "tag")]
+    dolor: HashSet<i32>,
+    #[setter(each = "datum")]
+    sit: HashMap<String, i32>,
+}
+
+#[test]
+fn each_setter_accumulates() {
+    let x = WithEachBuilder::default()
+        .item("a")
+        .item("b")
+        .tag(1)
+        .tag(2)
+        .datum("k", 3)
+        .build()
+        .unwrap();
+    assert_eq!(x.ipsum, vec!["a".to_string(), "b".to_string()]);
+    assert_eq!(x.dolor, [1, 2].iter().cloned().collect::<HashSet<_>>());
+    assert_eq!(x.sit.get("k"), Some(&3));
+}
+
+#[test]
+fn each_setter_defaults_to_empty_when_never_touched() {
+    let x = WithEachBuilder::default().build().unwrap();
+    assert!(x.ipsum.is_empty());
+    assert!(x.dolor.is_empty());
+    assert!(x.sit.is_empty());
+}
+
+#[derive(Builder, Debug, PartialEq, Clone)]
+#[setters(owned)]
+struct Owned {
+    ipsum: String,
+}
+
+#[test]
+fn owned_setters_chain_through_self() {
+    let x = OwnedBuilder::default().ipsum("sit").build().unwrap();
+    assert_eq!(x, Owned { ipsum: "sit".to_string() });
+}
+
+#[derive(Builder, Debug, PartialEq, Clone)]
+#[setters(immutable)]
+struct Immutable {
+    ipsum: String,
+}
+
+#[test]
+fn immutable_setters_return_a_new_builder() {
+    let b = ImmutableBuilder::default();
+    let x = b.ipsum("sit").build().unwrap();
+    assert_eq!(x, Immutable { ipsum: "sit".to_string() });
+}